@@ -0,0 +1,214 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll};
+
+use super::idb_transaction_listeners::{IdbTransactionListeners, WakerKey};
+use super::IdbTransactionResult;
+
+/// An RAII guard around a [web_sys::IdbTransaction], borrowing the
+/// commit/rollback ergonomics of `sqlx`'s `Transaction`.
+///
+/// Call [Self::commit] to request completion and await it, or [Self::abort]
+/// to cancel the transaction explicitly. IndexedDB transactions auto-commit
+/// once they go idle, so the guard only steps in on [Drop] if neither was
+/// called and the transaction hasn't already settled on its own — it's a
+/// safety net for a forgotten transaction, not a replacement for the
+/// browser's auto-commit.
+#[derive(Debug)]
+pub struct IdbTransactionGuard {
+    inner: web_sys::IdbTransaction,
+    listeners: Rc<IdbTransactionListeners>,
+    concluded: bool,
+}
+
+impl IdbTransactionGuard {
+    pub(crate) fn new(inner: web_sys::IdbTransaction, listeners: Rc<IdbTransactionListeners>) -> Self {
+        Self {
+            inner,
+            listeners,
+            concluded: false,
+        }
+    }
+
+    /// Requests that the transaction commit and returns a future resolving
+    /// to its [IdbTransactionResult].
+    pub fn commit(mut self) -> IdbTransactionDone {
+        self.concluded = true;
+        let _ = self.inner.commit();
+        IdbTransactionDone::new(self)
+    }
+
+    /// Aborts the transaction and returns a future resolving to its
+    /// [IdbTransactionResult].
+    pub fn abort(mut self) -> IdbTransactionDone {
+        self.concluded = true;
+        let _ = self.inner.abort();
+        IdbTransactionDone::new(self)
+    }
+}
+
+impl Drop for IdbTransactionGuard {
+    fn drop(&mut self) {
+        if !self.concluded && !self.listeners.has_settled() {
+            let _ = self.inner.abort();
+        }
+    }
+}
+
+/// Future returned by [IdbTransactionGuard::commit] and
+/// [IdbTransactionGuard::abort], resolving once the transaction settles.
+#[derive(Debug)]
+pub struct IdbTransactionDone {
+    guard: IdbTransactionGuard,
+    waker_key: WakerKey,
+}
+
+impl IdbTransactionDone {
+    fn new(guard: IdbTransactionGuard) -> Self {
+        Self {
+            guard,
+            waker_key: WakerKey::default(),
+        }
+    }
+}
+
+impl Future for IdbTransactionDone {
+    type Output = IdbTransactionResult;
+
+    fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        this.guard.listeners.do_poll(&this.waker_key, ctx)
+    }
+}
+
+impl Drop for IdbTransactionDone {
+    fn drop(&mut self) {
+        // If this future is dropped while still pending (e.g. it lost a
+        // `select!`/timeout race), deregister its slot instead of leaking it
+        // in the listeners' waker slab for the rest of the transaction.
+        if let Some(key) = self.waker_key.take() {
+            self.guard.listeners.release_waker(key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wasm_bindgen::prelude::*;
+    use wasm_bindgen::JsCast;
+    use wasm_bindgen_futures::JsFuture;
+    use wasm_bindgen_test::*;
+    use web_sys::{IdbDatabase, IdbOpenDbRequest, IdbTransactionMode};
+
+    use super::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    const STORE: &str = "store";
+
+    async fn open_test_db(name: &str) -> IdbDatabase {
+        let factory = web_sys::window().unwrap().indexed_db().unwrap().unwrap();
+        let open_req: IdbOpenDbRequest = factory.open(name).unwrap();
+
+        let on_upgrade = {
+            let req = open_req.clone();
+            Closure::once_into_js(move || {
+                let db: IdbDatabase = req.result().unwrap().unchecked_into();
+                if !db.object_store_names().contains(STORE) {
+                    db.create_object_store(STORE).unwrap();
+                }
+            })
+        };
+        open_req.set_onupgradeneeded(Some(on_upgrade.unchecked_ref()));
+
+        let promise = js_sys::Promise::new(&mut |resolve, reject| {
+            let req = open_req.clone();
+            let on_success = Closure::once_into_js(move || {
+                resolve.call1(&JsValue::NULL, &req.result().unwrap()).ok();
+            });
+            open_req.set_onsuccess(Some(on_success.unchecked_ref()));
+            let on_error = Closure::once_into_js(move |evt: web_sys::Event| {
+                reject.call1(&JsValue::NULL, &evt).ok();
+            });
+            open_req.set_onerror(Some(on_error.unchecked_ref()));
+        });
+        JsFuture::from(promise).await.unwrap().unchecked_into()
+    }
+
+    fn guard_for(db: &IdbDatabase) -> IdbTransactionGuard {
+        let txn = db
+            .transaction_with_str_and_mode(STORE, IdbTransactionMode::Readwrite)
+            .unwrap();
+        let listeners = Rc::new(IdbTransactionListeners::new(&txn));
+        IdbTransactionGuard::new(txn, listeners)
+    }
+
+    #[wasm_bindgen_test]
+    async fn commit_resolves_with_success() {
+        let db = open_test_db("idb_transaction_guard_commit").await;
+        let guard = guard_for(&db);
+
+        let result = guard.commit().await;
+
+        assert!(matches!(result, IdbTransactionResult::Success));
+    }
+
+    #[wasm_bindgen_test]
+    async fn abort_resolves_with_a_user_initiated_abort() {
+        let db = open_test_db("idb_transaction_guard_abort").await;
+        let guard = guard_for(&db);
+
+        let result = guard.abort().await;
+
+        assert!(matches!(result, IdbTransactionResult::Abort(None)));
+    }
+
+    #[wasm_bindgen_test]
+    async fn engine_forced_abort_carries_the_dom_exception() {
+        let db = open_test_db("idb_transaction_guard_engine_abort").await;
+        let txn = db
+            .transaction_with_str_and_mode(STORE, IdbTransactionMode::Readwrite)
+            .unwrap();
+        let listeners = IdbTransactionListeners::new(&txn);
+        let store = txn.object_store(STORE).unwrap();
+
+        // Adding the same out-of-line key twice violates the store's
+        // uniqueness constraint, so the engine aborts the transaction with
+        // a `ConstraintError` rather than anyone calling `abort()`.
+        store
+            .add_with_key(&JsValue::from_str("value"), &JsValue::from_str("dup"))
+            .unwrap();
+        store
+            .add_with_key(&JsValue::from_str("value"), &JsValue::from_str("dup"))
+            .unwrap();
+
+        let waker_key = WakerKey::default();
+        let result = std::future::poll_fn(|ctx| listeners.do_poll(&waker_key, ctx)).await;
+
+        match result {
+            IdbTransactionResult::Abort(Some(err)) => assert_eq!(err.name(), "ConstraintError"),
+            other => panic!("expected an engine-forced abort, got {other:?}"),
+        }
+    }
+
+    #[wasm_bindgen_test]
+    async fn dropping_without_commit_or_abort_aborts_the_transaction() {
+        let db = open_test_db("idb_transaction_guard_drop").await;
+        let txn = db
+            .transaction_with_str_and_mode(STORE, IdbTransactionMode::Readwrite)
+            .unwrap();
+        let listeners = Rc::new(IdbTransactionListeners::new(&txn));
+
+        {
+            // Dropped without calling commit() or abort() on purpose.
+            let _guard = IdbTransactionGuard::new(txn, Rc::clone(&listeners));
+        }
+
+        let waker_key = WakerKey::default();
+        let result =
+            std::future::poll_fn(|ctx| listeners.do_poll(&waker_key, ctx)).await;
+
+        assert!(matches!(result, IdbTransactionResult::Abort(None)));
+    }
+}
@@ -1,3 +1,4 @@
+use std::cell::Cell;
 use std::ops::Deref;
 use std::task::Poll;
 use std::{
@@ -6,43 +7,55 @@ use std::{
     task::{Context, Waker},
 };
 
+use slab::Slab;
 use wasm_bindgen::{prelude::*, JsCast};
 
-use crate::internal_utils::{create_lazy_ref_cell, wake};
+use crate::internal_utils::create_lazy_ref_cell;
 
 use super::IdbTransactionResult;
 
 type Cb = dyn Fn() + 'static;
 type ErrCb = dyn Fn(web_sys::Event) + 'static;
-type WakerRef = Rc<RefCell<Option<Waker>>>;
+type WakerSlab = Rc<RefCell<Slab<Option<Waker>>>>;
 type ResultRef = Rc<RefCell<Option<IdbTransactionResult>>>;
 
+/// A handle into an [IdbTransactionListeners]'s waker slab, identifying a
+/// single awaiter's registered [Waker] so it can be updated in place across
+/// polls instead of being overwritten by unrelated awaiters.
+pub(crate) type WakerKey = Cell<Option<usize>>;
+
 /// IdbTransaction event listeners
+///
+/// Multiple futures may clone the same underlying transaction handle and
+/// await its completion independently (e.g. via a `.shared()`-style API).
+/// To support that, wakers are kept in a [Slab] keyed by a stable
+/// [WakerKey] handed to each awaiter on its first poll, rather than a
+/// single slot that the most recent awaiter would otherwise clobber.
 #[derive(Debug)]
 pub(crate) struct IdbTransactionListeners {
-    waker: WakerRef,
+    wakers: WakerSlab,
     result: ResultRef,
     on_success: Closure<Cb>,
-    on_abort: Closure<Cb>,
+    on_abort: Closure<ErrCb>,
     on_error: Closure<ErrCb>,
 }
 
 impl IdbTransactionListeners {
     pub fn new(inner: &web_sys::IdbTransaction) -> Self {
-        let waker = create_lazy_ref_cell();
+        let wakers: WakerSlab = Default::default();
         let result = create_lazy_ref_cell();
 
         let on_success =
-            base_callback(waker.clone(), result.clone(), IdbTransactionResult::Success);
-        let on_error = error_callback(waker.clone(), result.clone());
-        let on_abort = base_callback(waker.clone(), result.clone(), IdbTransactionResult::Abort);
+            base_callback(wakers.clone(), result.clone(), IdbTransactionResult::Success);
+        let on_error = error_callback(wakers.clone(), result.clone());
+        let on_abort = abort_callback(wakers.clone(), result.clone());
 
         inner.set_oncomplete(Some(on_success.as_ref().unchecked_ref()));
         inner.set_onerror(Some(on_error.as_ref().unchecked_ref()));
         inner.set_onabort(Some(on_abort.as_ref().unchecked_ref()));
 
         Self {
-            waker,
+            wakers,
             result,
             on_error,
             on_success,
@@ -50,18 +63,56 @@ impl IdbTransactionListeners {
         }
     }
 
-    pub fn do_poll(&self, ctx: &Context<'_>) -> Poll<IdbTransactionResult> {
+    /// Polls for the transaction result on behalf of a single awaiter,
+    /// identified by `waker_key`. On an awaiter's first call, `waker_key`
+    /// should hold `None`; this registers a new slot in the waker slab and
+    /// writes its key back so later polls update that same slot rather
+    /// than leaking a new one per poll.
+    pub fn do_poll(&self, waker_key: &WakerKey, ctx: &Context<'_>) -> Poll<IdbTransactionResult> {
         if let Some(v) = self.result.borrow().deref() {
+            if let Some(key) = waker_key.take() {
+                self.wakers.borrow_mut().try_remove(key);
+            }
             Poll::Ready(v.clone())
         } else {
-            self.waker.borrow_mut().replace(ctx.waker().clone());
+            let waker = ctx.waker().clone();
+            let mut wakers = self.wakers.borrow_mut();
+            match waker_key.get() {
+                Some(key) => wakers[key] = Some(waker),
+                None => waker_key.set(Some(wakers.insert(Some(waker)))),
+            }
             Poll::Pending
         }
     }
+
+    /// Returns whether the transaction has already settled (completed,
+    /// aborted, or errored), independently of any particular awaiter.
+    pub fn has_settled(&self) -> bool {
+        self.result.borrow().is_some()
+    }
+
+    /// Removes a previously registered waker slot. Awaiters must call this
+    /// if they're dropped while still pending, mirroring how
+    /// `futures_util::Shared` deregisters a clone's waker on drop — without
+    /// it, a future that's dropped mid-poll (e.g. losing a `select!` race)
+    /// would leak its slab slot and a stale [Waker] for the transaction's
+    /// remaining lifetime.
+    pub fn release_waker(&self, key: usize) {
+        self.wakers.borrow_mut().try_remove(key);
+    }
 }
 
-fn error_callback(waker: WakerRef, result: ResultRef) -> Closure<ErrCb> {
-    /// Returns true if the waker should be called
+/// Wakes and clears every waker currently registered in the slab.
+fn wake_all(wakers: &WakerSlab) {
+    for (_, waker) in wakers.borrow_mut().iter_mut() {
+        if let Some(waker) = waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+fn error_callback(wakers: WakerSlab, result: ResultRef) -> Closure<ErrCb> {
+    /// Returns true if the wakers should be called
     fn process(evt: web_sys::Event, result: &ResultRef) -> bool {
         let req: web_sys::IdbRequest = match evt.target() {
             Some(t) => t.unchecked_into(),
@@ -92,14 +143,46 @@ fn error_callback(waker: WakerRef, result: ResultRef) -> Closure<ErrCb> {
     }
     let b = Box::new(move |e: web_sys::Event| {
         if process(e, &result) {
-            wake(&waker);
+            wake_all(&wakers);
+        }
+    });
+    Closure::wrap(b)
+}
+
+fn abort_callback(wakers: WakerSlab, result: ResultRef) -> Closure<ErrCb> {
+    /// Returns true if the wakers should be called
+    fn process(evt: web_sys::Event, result: &ResultRef) -> bool {
+        // The engine sets `transaction.error` when it aborts a transaction
+        // itself (e.g. `QuotaExceededError`, `ConstraintError`); a
+        // user-initiated `abort()` leaves it `None`.
+        let err = evt
+            .target()
+            .map(|t| t.unchecked_into::<web_sys::IdbTransaction>())
+            .and_then(|txn| txn.error());
+
+        let mut result_ref = if let Ok(result_ref) = result.try_borrow_mut() {
+            result_ref
+        } else {
+            return false;
+        };
+
+        if result_ref.is_none() {
+            result_ref.replace(IdbTransactionResult::Abort(err));
+            true
+        } else {
+            false
+        }
+    }
+    let b = Box::new(move |e: web_sys::Event| {
+        if process(e, &result) {
+            wake_all(&wakers);
         }
     });
     Closure::wrap(b)
 }
 
-fn base_callback(waker: WakerRef, result: ResultRef, kind: IdbTransactionResult) -> Closure<Cb> {
-    /// Returns true if the waker should be called
+fn base_callback(wakers: WakerSlab, result: ResultRef, kind: IdbTransactionResult) -> Closure<Cb> {
+    /// Returns true if the wakers should be called
     fn process(result: &ResultRef, kind: IdbTransactionResult) -> bool {
         let mut result_ref = if let Ok(v) = result.try_borrow_mut() {
             v
@@ -118,8 +201,80 @@ fn base_callback(waker: WakerRef, result: ResultRef, kind: IdbTransactionResult)
     let b = Box::new(move || {
         if process(&result, kind.clone()) {
             // Clone so this can be Fn and not FnOnce
-            wake(&waker);
+            wake_all(&wakers);
         }
     });
     Closure::wrap(b)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future::poll_fn;
+
+    use futures_util::{join, poll};
+    use wasm_bindgen::prelude::*;
+    use wasm_bindgen_futures::JsFuture;
+    use wasm_bindgen_test::*;
+    use web_sys::{IdbDatabase, IdbOpenDbRequest, IdbTransactionMode};
+
+    use super::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    const STORE: &str = "store";
+
+    async fn open_test_db(name: &str) -> IdbDatabase {
+        let factory = web_sys::window().unwrap().indexed_db().unwrap().unwrap();
+        let open_req: IdbOpenDbRequest = factory.open(name).unwrap();
+
+        let on_upgrade = {
+            let req = open_req.clone();
+            Closure::once_into_js(move || {
+                let db: IdbDatabase = req.result().unwrap().unchecked_into();
+                if !db.object_store_names().contains(STORE) {
+                    db.create_object_store(STORE).unwrap();
+                }
+            })
+        };
+        open_req.set_onupgradeneeded(Some(on_upgrade.unchecked_ref()));
+
+        let promise = js_sys::Promise::new(&mut |resolve, reject| {
+            let req = open_req.clone();
+            let on_success = Closure::once_into_js(move || {
+                resolve.call1(&JsValue::NULL, &req.result().unwrap()).ok();
+            });
+            open_req.set_onsuccess(Some(on_success.unchecked_ref()));
+            let on_error = Closure::once_into_js(move |evt: web_sys::Event| {
+                reject.call1(&JsValue::NULL, &evt).ok();
+            });
+            open_req.set_onerror(Some(on_error.unchecked_ref()));
+        });
+        JsFuture::from(promise).await.unwrap().unchecked_into()
+    }
+
+    #[wasm_bindgen_test]
+    async fn wake_all_wakes_every_registered_waker() {
+        let db = open_test_db("idb_transaction_listeners_multi_waker").await;
+        let txn = db
+            .transaction_with_str_and_mode(STORE, IdbTransactionMode::Readwrite)
+            .unwrap();
+        let listeners = IdbTransactionListeners::new(&txn);
+
+        let key_a = WakerKey::default();
+        let key_b = WakerKey::default();
+
+        // Register two independent awaiters against the same listeners
+        // before the transaction settles, as multiple clones of a
+        // `.shared()`-style handle would.
+        let mut poll_a = Box::pin(poll_fn(|ctx| listeners.do_poll(&key_a, ctx)));
+        let mut poll_b = Box::pin(poll_fn(|ctx| listeners.do_poll(&key_b, ctx)));
+        assert!(poll!(poll_a.as_mut()).is_pending());
+        assert!(poll!(poll_b.as_mut()).is_pending());
+
+        let _ = txn.commit();
+
+        let (result_a, result_b) = join!(poll_a, poll_b);
+        assert!(matches!(result_a, IdbTransactionResult::Success));
+        assert!(matches!(result_b, IdbTransactionResult::Success));
+    }
+}
@@ -0,0 +1,22 @@
+mod idb_transaction_guard;
+mod idb_transaction_listeners;
+
+pub use idb_transaction_guard::{IdbTransactionDone, IdbTransactionGuard};
+pub(crate) use idb_transaction_listeners::IdbTransactionListeners;
+
+use web_sys::DomException;
+
+/// The final outcome of an [IdbTransaction][web_sys::IdbTransaction].
+#[derive(Debug, Clone)]
+pub enum IdbTransactionResult {
+    /// The transaction completed successfully.
+    Success,
+    /// The transaction was aborted. Carries the causing [DomException] (e.g.
+    /// `QuotaExceededError`, `ConstraintError`) when the engine forced the
+    /// abort; `None` when it was user-initiated via
+    /// [IdbTransactionGuard::abort].
+    Abort(Option<DomException>),
+    /// A request within the transaction errored, causing the transaction to
+    /// fail.
+    Error(DomException),
+}
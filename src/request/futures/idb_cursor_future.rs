@@ -54,6 +54,15 @@ impl<'a, T: IdbQuerySource> IdbCursorFuture<'a, T> {
         };
         Ok(opt)
     }
+
+    /// Re-arms the underlying request so it can be polled again for the
+    /// next `onsuccess`. IndexedDB fires `onsuccess` again on the same
+    /// request after `cursor.continue()`/`advance()`, so [IdbCursorStream]
+    /// uses this to drive repeated polls over one request rather than
+    /// creating a new one per item.
+    pub(crate) fn rearm(&self) {
+        self.inner.reset();
+    }
 }
 
 impl<'a, T: IdbQuerySource> Future for IdbCursorFuture<'a, T> {
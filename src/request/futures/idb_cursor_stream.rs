@@ -0,0 +1,79 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_util::stream::Stream;
+use web_sys::DomException;
+
+use crate::idb_cursor::IdbCursor;
+use crate::idb_query_source::IdbQuerySource;
+
+use super::IdbCursorFuture;
+
+/// A [Stream][futures_util::stream::Stream] over the results of an
+/// [IdbCursor], driving `continue`/`advance` internally so callers can
+/// iterate with `while let Some(cursor) = stream.try_next().await?`
+/// instead of manually re-awaiting after every step.
+///
+/// IndexedDB reuses the same underlying request across `cursor.continue()`
+/// calls, firing `onsuccess` again each time rather than handing out a new
+/// request. Advancing eagerly, in the same `poll_next` call that hands a
+/// cursor back, would let the shared native cursor overwrite its own
+/// cached key/value while the caller still holds what it thinks is the
+/// current row — so [Self::poll_next] instead records the cursor as
+/// pending advance and only advances/re-arms it on entry to the *next*
+/// `poll_next` call, before polling for the following `onsuccess`. The
+/// stream ends once the request resolves to a null cursor, mirroring how
+/// [IdbCursorFuture] resolves to `None`.
+///
+/// Features required: `cursors`, `streams`
+#[derive(Debug)]
+pub struct IdbCursorStream<'a, T: IdbQuerySource> {
+    inner: IdbCursorFuture<'a, T>,
+    done: bool,
+    pending_advance: Option<IdbCursor<'a, T>>,
+}
+
+impl<'a, T: IdbQuerySource> IdbCursorStream<'a, T> {
+    pub(crate) fn new(inner: IdbCursorFuture<'a, T>) -> Self {
+        Self {
+            inner,
+            done: false,
+            pending_advance: None,
+        }
+    }
+}
+
+impl<'a, T: IdbQuerySource> Stream for IdbCursorStream<'a, T> {
+    type Item = Result<IdbCursor<'a, T>, DomException>;
+
+    fn poll_next(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        if this.done {
+            return Poll::Ready(None);
+        }
+
+        if let Some(cursor) = this.pending_advance.take() {
+            if let Err(err) = cursor.advance_raw(1) {
+                this.done = true;
+                return Poll::Ready(Some(Err(err)));
+            }
+            this.inner.rearm();
+        }
+
+        this.inner.do_poll(ctx).map(|res| match res {
+            Ok(None) => {
+                this.done = true;
+                None
+            }
+            Err(err) => {
+                this.done = true;
+                Some(Err(err))
+            }
+            Ok(Some(cursor)) => {
+                this.pending_advance = Some(cursor.clone());
+                Some(Ok(cursor))
+            }
+        })
+    }
+}